@@ -0,0 +1,101 @@
+/// Describes the layout of a single vertex attribute in a vertex buffer.
+///
+/// Most variants are named after their component type and count (eg. `U8U8U8U8` is four
+/// `u8`s). The `Norm` suffix means that integer components should be read by the GPU as
+/// normalized floats (eg. `[0, 255]` -> `[0.0, 1.0]` for unsigned types).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AttributeType {
+    I8,
+    I8I8,
+    I8I8I8,
+    I8I8I8I8,
+    I8I8I8I8Norm,
+    U8,
+    U8U8,
+    U8U8U8,
+    U8U8U8U8,
+    U8U8U8U8Norm,
+    I16,
+    I16I16,
+    I16I16I16,
+    I16I16I16I16,
+    U16,
+    U16U16,
+    U16U16U16,
+    U16U16U16U16,
+    I32,
+    I32I32,
+    I32I32I32,
+    I32I32I32I32,
+    U32,
+    U32U32,
+    U32U32U32,
+    U32U32U32U32,
+    /// A single `GL_INT_2_10_10_10_REV`-packed value, read as 4 normalized components.
+    I32_2_10_10_10Norm,
+    /// A single `GL_UNSIGNED_INT_2_10_10_10_REV`-packed value, read as 4 normalized components.
+    U32_2_10_10_10Norm,
+    F32,
+    F32F32,
+    F32F32F32,
+    F32F32F32F32,
+    F32x2x2,
+    F32x2x3,
+    F32x2x4,
+    F32x3x2,
+    F32x3x3,
+    F32x3x4,
+    F32x4x2,
+    F32x4x3,
+    F32x4x4,
+    F64,
+    F64F64,
+    F64F64F64,
+    F64F64F64F64,
+    F64x2x2,
+    F64x2x3,
+    F64x2x4,
+    F64x3x2,
+    F64x3x3,
+    F64x3x4,
+    F64x4x2,
+    F64x4x3,
+    F64x4x4,
+}
+
+impl AttributeType {
+    /// Returns the number of components of this type (eg. `3` for `F32F32F32`).
+    pub fn get_num_components(&self) -> usize {
+        match *self {
+            AttributeType::I8 | AttributeType::U8 | AttributeType::I16 | AttributeType::U16 |
+            AttributeType::I32 | AttributeType::U32 | AttributeType::F32 |
+            AttributeType::F64 => 1,
+
+            AttributeType::I8I8 | AttributeType::U8U8 | AttributeType::I16I16 |
+            AttributeType::U16U16 | AttributeType::I32I32 | AttributeType::U32U32 |
+            AttributeType::F32F32 | AttributeType::F64F64 => 2,
+
+            AttributeType::I8I8I8 | AttributeType::U8U8U8 | AttributeType::I16I16I16 |
+            AttributeType::U16U16U16 | AttributeType::I32I32I32 | AttributeType::U32U32U32 |
+            AttributeType::F32F32F32 | AttributeType::F64F64F64 => 3,
+
+            AttributeType::I8I8I8I8 | AttributeType::I8I8I8I8Norm | AttributeType::U8U8U8U8 |
+            AttributeType::U8U8U8U8Norm | AttributeType::I16I16I16I16 |
+            AttributeType::U16U16U16U16 | AttributeType::I32I32I32I32 |
+            AttributeType::U32U32U32U32 | AttributeType::I32_2_10_10_10Norm |
+            AttributeType::U32_2_10_10_10Norm | AttributeType::F32F32F32F32 |
+            AttributeType::F64F64F64F64 => 4,
+
+            AttributeType::F32x2x2 | AttributeType::F64x2x2 => 4,
+            AttributeType::F32x2x3 | AttributeType::F32x3x2 |
+            AttributeType::F64x2x3 | AttributeType::F64x3x2 => 6,
+            AttributeType::F32x2x4 | AttributeType::F32x4x2 |
+            AttributeType::F64x2x4 | AttributeType::F64x4x2 => 8,
+            AttributeType::F32x3x3 | AttributeType::F64x3x3 => 9,
+            AttributeType::F32x3x4 | AttributeType::F32x4x3 |
+            AttributeType::F64x3x4 | AttributeType::F64x4x3 => 12,
+            AttributeType::F32x4x4 | AttributeType::F64x4x4 => 16,
+        }
+    }
+}
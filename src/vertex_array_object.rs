@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::hash::Hash;
 use std::mem;
 
 use Handle;
@@ -19,7 +20,33 @@ use version::Version;
 pub struct VertexAttributesSystem {
     // we maintain a list of VAOs for each vertexbuffer-indexbuffer-program association
     // the key is a (buffers-list, program) ; the buffers list must be sorted
-    vaos: RefCell<HashMap<(Vec<gl::types::GLuint>, Handle), VertexArrayObject>>,
+    //
+    // each entry in the buffers list is a (buffer id, first, divisor) triplet: `first` and
+    // `divisor` are baked into the VAO's attribute pointers, so two bindings of the same
+    // buffer with a different `first` or `divisor` must not share a VAO
+    vaos: RefCell<HashMap<(Vec<(gl::types::GLuint, usize, Option<u32>)>, Handle), VaoCacheEntry>>,
+
+    // VAOs built with the `ARB_vertex_attrib_binding` fast path. Unlike `vaos` above, none of
+    // the actual buffers are baked into these: only each buffer's (format, stride, divisor) is,
+    // which is why the key is a `FastPathKey` instead of a list of buffer ids. The buffers
+    // themselves are attached with `glBindVertexBuffer` right before every draw. The key is
+    // cheap to build directly from `Binder::add`'s arguments, so a cache hit never has to walk
+    // the program's attributes to find out it already has a matching VAO.
+    format_vaos: RefCell<HashMap<(FastPathKey, Handle), VaoCacheEntry>>,
+
+    // maximum number of entries kept in *each* of the two caches above before the
+    // least-recently-bound one gets evicted; `usize::MAX` (the default) disables eviction
+    capacity: Cell<usize>,
+
+    // monotonic counter bumped on every bind, used to timestamp `VaoCacheEntry::last_used`
+    clock: Cell<u64>,
+}
+
+/// A cached VAO together with the tick (see `VertexAttributesSystem::clock`) at which it was
+/// last bound, used to pick an eviction candidate when a cache is full.
+struct VaoCacheEntry {
+    vao: VertexArrayObject,
+    last_used: u64,
 }
 
 /// Object allowing one to bind vertex attributes to the current context.
@@ -28,7 +55,7 @@ pub struct Binder<'a, 'c, 'd: 'c> {
     program: &'a Program,
     system: &'a VertexAttributesSystem,
     element_array_buffer: gl::types::GLuint,
-    vertex_buffers: Vec<(gl::types::GLuint, VertexFormat, usize, Option<u32>)>,
+    vertex_buffers: Vec<(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)>,
 }
 
 impl VertexAttributesSystem {
@@ -36,6 +63,43 @@ impl VertexAttributesSystem {
     pub fn new() -> VertexAttributesSystem {
         VertexAttributesSystem {
             vaos: RefCell::new(HashMap::new()),
+            format_vaos: RefCell::new(HashMap::new()),
+            capacity: Cell::new(usize::max_value()),
+            clock: Cell::new(0),
+        }
+    }
+
+    /// Sets the maximum number of VAOs kept in each of the two internal caches. Before a new VAO
+    /// is inserted into a cache that already holds `capacity` entries or more, the
+    /// least-recently-bound ones are destroyed to make room, so the cache never grows past
+    /// `capacity` (a capacity of `0` behaves like `1`: the VAO just bound is kept). Defaults to
+    /// `usize::MAX`, ie. caches grow without bound, which matches the previous behaviour; pass
+    /// a smaller value to bound GPU object growth in applications that churn through many
+    /// buffer/program combinations.
+    pub fn set_vaos_cache_capacity(&self, capacity: usize) {
+        self.capacity.set(capacity);
+    }
+
+    /// Returns the next value of the internal clock, used to timestamp cache entries.
+    fn tick(&self) -> u64 {
+        let tick = self.clock.get();
+        self.clock.set(tick.wrapping_add(1));
+        tick
+    }
+
+    /// Evicts the least-recently-bound entries of `map` until it holds fewer than `capacity`
+    /// entries, so that the insert the caller makes right after this call never leaves `map`
+    /// over capacity (for any `capacity` of at least 1).
+    fn evict_lru<K: Eq + Hash + Clone>(ctxt: &mut CommandContext, map: &mut HashMap<K, VaoCacheEntry>,
+                                        capacity: usize)
+    {
+        while map.len() >= capacity {
+            let lru_key = map.iter().min_by_key(|&(_, entry)| entry.last_used).map(|(k, _)| k.clone());
+
+            match lru_key {
+                Some(key) => map.remove(&key).unwrap().vao.destroy(ctxt),
+                None => break,
+            }
         }
     }
 
@@ -55,41 +119,49 @@ impl VertexAttributesSystem {
     /// This function *must* be called whenever you destroy a buffer so that the system can
     /// purge its VAOs cache.
     pub fn purge_buffer(&self, ctxt: &mut CommandContext, id: gl::types::GLuint) {
+        // `format_vaos` never bakes a buffer id in its key, so only the legacy cache is affected
         self.purge_if(ctxt, |&(ref buffers, _)| {
-            buffers.iter().find(|&b| b == &id).is_some()
+            buffers.iter().find(|&&(b, _, _)| b == id).is_some()
         })
     }
 
     /// This function *must* be called whenever you destroy a program so that the system can
     /// purge its VAOs cache.
     pub fn purge_program(&self, ctxt: &mut CommandContext, program: Handle) {
-        self.purge_if(ctxt, |&(_, p)| p == program)
+        self.purge_if(ctxt, |&(_, p)| p == program);
+        self.purge_format_if(ctxt, |&(_, p)| p == program);
     }
 
     /// Purges the VAOs cache.
     pub fn purge_all(&self, ctxt: &mut CommandContext) {
-        let vaos = mem::replace(&mut *self.vaos.borrow_mut(),
-                                HashMap::new());
+        let vaos = mem::replace(&mut *self.vaos.borrow_mut(), HashMap::new());
+        for (_, entry) in vaos {
+            entry.vao.destroy(ctxt);
+        }
 
-        for (_, vao) in vaos {
-            vao.destroy(ctxt);
+        let format_vaos = mem::replace(&mut *self.format_vaos.borrow_mut(), HashMap::new());
+        for (_, entry) in format_vaos {
+            entry.vao.destroy(ctxt);
         }
     }
 
     /// Purges the VAOs cache. Contrary to `purge_all`, this function expects the system to be
     /// destroyed soon.
     pub fn cleanup(&mut self, ctxt: &mut CommandContext) {
-        let vaos = mem::replace(&mut *self.vaos.borrow_mut(),
-                                HashMap::with_capacity(0));
+        let vaos = mem::replace(&mut *self.vaos.borrow_mut(), HashMap::with_capacity(0));
+        for (_, entry) in vaos {
+            entry.vao.destroy(ctxt);
+        }
 
-        for (_, vao) in vaos {
-            vao.destroy(ctxt);
+        let format_vaos = mem::replace(&mut *self.format_vaos.borrow_mut(), HashMap::with_capacity(0));
+        for (_, entry) in format_vaos {
+            entry.vao.destroy(ctxt);
         }
     }
 
-    /// Purges VAOs that match a certain condition.
+    /// Purges VAOs from the legacy cache that match a certain condition.
     fn purge_if<F>(&self, ctxt: &mut CommandContext, mut condition: F)
-                   where F: FnMut(&(Vec<gl::types::GLuint>, Handle)) -> bool
+                   where F: FnMut(&(Vec<(gl::types::GLuint, usize, Option<u32>)>, Handle)) -> bool
     {
         let mut vaos = self.vaos.borrow_mut();
 
@@ -101,7 +173,25 @@ impl VertexAttributesSystem {
         }
 
         for key in keys {
-            vaos.remove(&key).unwrap().destroy(ctxt);
+            vaos.remove(&key).unwrap().vao.destroy(ctxt);
+        }
+    }
+
+    /// Purges VAOs from the fast-path cache that match a certain condition.
+    fn purge_format_if<F>(&self, ctxt: &mut CommandContext, mut condition: F)
+                          where F: FnMut(&(FastPathKey, Handle)) -> bool
+    {
+        let mut format_vaos = self.format_vaos.borrow_mut();
+
+        let mut keys = Vec::with_capacity(4);
+        for (key, _) in &*format_vaos {
+            if condition(key) {
+                keys.push(key.clone());
+            }
+        }
+
+        for key in keys {
+            format_vaos.remove(&key).unwrap().vao.destroy(ctxt);
         }
     }
 }
@@ -117,27 +207,41 @@ impl<'a, 'c, 'd: 'c> Binder<'a, 'c, 'd> {
     pub fn add(mut self, buffer: &VertexBufferAny, first: usize, divisor: Option<u32>)
                -> Binder<'a, 'c, 'd>
     {
-        assert!(first == 0);       // TODO: not implemented
-
         let (buffer, format, stride) = (buffer.get_id(), buffer.get_bindings().clone(),
                                         buffer.get_elements_size());
 
-        self.vertex_buffers.push((buffer, format, stride, divisor));
+        self.vertex_buffers.push((buffer, format, stride, first, divisor));
         self
     }
 
     /// Finish binding the vertex attributes.
     pub fn bind(self) {
-        let mut buffers_list: Vec<_> = self.vertex_buffers.iter().map(|&(v, _, _, _)| v).collect();
-        buffers_list.push(self.element_array_buffer);
+        let fast_path = self.context.version >= &Version(Api::Gl, 4, 3) ||
+                         self.context.extensions.gl_arb_vertex_attrib_binding;
+
+        if fast_path {
+            self.bind_fast_path();
+        } else {
+            self.bind_legacy();
+        }
+    }
+
+    /// Binds using one VAO per `(buffers, program)` combination, with the buffers baked into
+    /// the VAO's attribute pointers. Used as a fallback when `ARB_vertex_attrib_binding` (or
+    /// GL 4.3) isn't available.
+    fn bind_legacy(self) {
+        let mut buffers_list: Vec<_> = self.vertex_buffers.iter()
+                                           .map(|&(v, _, _, first, divisor)| (v, first, divisor))
+                                           .collect();
+        buffers_list.push((self.element_array_buffer, 0, None));
         buffers_list.sort();
 
         let program_id = self.program.get_id();
+        let key = (buffers_list, program_id);
 
-        if let Some(value) = self.system.vaos.borrow_mut()
-                                 .get(&(buffers_list.clone(), program_id))
-        {
-            bind_vao(self.context, value.id);
+        if let Some(entry) = self.system.vaos.borrow_mut().get_mut(&key) {
+            entry.last_used = self.system.tick();
+            bind_vao(self.context, entry.vao.id);
             return;
         }
 
@@ -146,7 +250,52 @@ impl<'a, 'c, 'd: 'c> Binder<'a, 'c, 'd> {
                                    self.element_array_buffer, self.program)
         };
         bind_vao(self.context, new_vao.id);
-        self.system.vaos.borrow_mut().insert((buffers_list, program_id), new_vao);
+
+        let mut vaos = self.system.vaos.borrow_mut();
+        VertexAttributesSystem::evict_lru(self.context, &mut vaos, self.system.capacity.get());
+        let last_used = self.system.tick();
+        vaos.insert(key, VaoCacheEntry { vao: new_vao, last_used: last_used });
+    }
+
+    /// Binds using one format-only VAO per `(format, program)` combination, and attaches the
+    /// actual buffers with `glBindVertexBuffer` on every call. This lets the same VAO be reused
+    /// across draws that share a vertex layout but use different buffers.
+    fn bind_fast_path(self) {
+        // cheap to build: no program attribute lookup, no `VaoAttribute` allocation. This is
+        // what makes a cache hit fast — `validate_bindings`/`build_vao_format` below only run
+        // when this key is new, not on every draw.
+        let key = (FastPathKey {
+            buffers: self.vertex_buffers.iter()
+                         .map(|&(_, ref fmt, stride, _, divisor)| (fmt.clone(), stride, divisor))
+                         .collect(),
+        }, self.program.get_id());
+
+        if !self.system.format_vaos.borrow().contains_key(&key) {
+            validate_bindings(&self.vertex_buffers, self.program);
+            let format = build_vao_format(&self.vertex_buffers, self.program);
+            let new_vao = unsafe { VertexArrayObject::new_fast_path(self.context, &format) };
+
+            let mut format_vaos = self.system.format_vaos.borrow_mut();
+            VertexAttributesSystem::evict_lru(self.context, &mut format_vaos, self.system.capacity.get());
+            let last_used = self.system.tick();
+            format_vaos.insert(key.clone(), VaoCacheEntry { vao: new_vao, last_used: last_used });
+        }
+
+        {
+            let mut format_vaos = self.system.format_vaos.borrow_mut();
+            let entry = format_vaos.get_mut(&key).unwrap();
+            entry.last_used = self.system.tick();
+            bind_vao(self.context, entry.vao.id);
+        }
+
+        unsafe {
+            bind_element_array_buffer(self.context, self.element_array_buffer);
+
+            for (binding, &(buffer, _, stride, first, _)) in self.vertex_buffers.iter().enumerate() {
+                self.context.gl.BindVertexBuffer(binding as u32, buffer,
+                    (first * stride) as gl::types::GLintptr, stride as gl::types::GLsizei);
+            }
+        }
     }
 }
 
@@ -156,65 +305,215 @@ struct VertexArrayObject {
     destroyed: bool,
 }
 
+/// Cheap cache key for the `ARB_vertex_attrib_binding` fast path: the per-buffer (format,
+/// stride, divisor) that `VaoFormat` is built from, without needing the program's attributes.
+/// Order matters, since `build_vao_format` assigns binding indices by position in `buffers`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FastPathKey {
+    buffers: Vec<(VertexFormat, usize, Option<u32>)>,
+}
+
+/// Describes the attribute layout of a `(buffers, program)` combination, independently of
+/// which actual buffers are bound. Built by `build_vao_format` from the vertex buffers and a
+/// program on a cache miss.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct VaoFormat {
+    attributes: Vec<VaoAttribute>,
+    // (stride, divisor) of each vertex buffer, indexed by binding index
+    bindings: Vec<(usize, Option<u32>)>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct VaoAttribute {
+    location: i32,
+    // the program attribute's own GL type, used to pick between VertexAttribFormat/IFormat/LFormat
+    dispatch: gl::types::GLenum,
+    data_type: gl::types::GLenum,
+    count: gl::types::GLint,
+    normalized: bool,
+    relative_offset: u32,
+    binding: u32,
+}
+
+/// Checks that a set of vertex buffers matches a program's attributes, and panics otherwise.
+/// Shared between the legacy and fast VAO construction paths, since both need the same
+/// guarantees before touching the GL state.
+fn validate_bindings(vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
+                      program: &Program)
+{
+    // checking the attributes types
+    for &(_, ref bindings, _, _, _) in vertex_buffers {
+        for &(ref name, _, ty) in bindings {
+            let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+                Some(a) => a,
+                None => continue
+            };
+
+            let (_, ty_components, _) = vertex_location_type_to_gl(ty);
+            let (_, attribute_components, _) = vertex_location_type_to_gl(attribute.ty);
+            let ty_locations = attribute_location_count(ty, 1);
+            let attribute_locations = attribute_location_count(attribute.ty, attribute.size);
+
+            if ty_components != attribute_components || ty_locations != attribute_locations {
+                panic!("The program attribute `{}` does not match the vertex format. \
+                        Program expected {:?}, got {:?}.", name, attribute.ty, ty);
+            }
+        }
+    }
+
+    // checking for missing attributes
+    for (&ref name, _) in program.attributes() {
+        let mut found = false;
+        for &(_, ref bindings, _, _, _) in vertex_buffers {
+            if bindings.iter().find(|&&(ref n, _, _)| n == name).is_some() {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            panic!("The program attribute `{}` is missing in the vertex bindings", name);
+        }
+    }
+
+    // TODO: check for collisions between the vertices sources
+}
+
+/// Builds the format-only description of a set of vertex buffers for a given program, assigning
+/// each vertex buffer a binding index equal to its position in `vertex_buffers`.
+fn build_vao_format(vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
+                     program: &Program) -> VaoFormat
+{
+    let mut attributes = Vec::new();
+    let mut bindings = Vec::with_capacity(vertex_buffers.len());
+
+    for (binding_index, &(_, ref fmt, stride, _, divisor)) in vertex_buffers.iter().enumerate() {
+        bindings.push((stride, divisor));
+
+        for &(ref name, offset, ty) in fmt {
+            let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+                Some(a) => a,
+                None => continue
+            };
+
+            if attribute.location == -1 {
+                continue;
+            }
+
+            let (data_type, count, normalized) = vertex_location_type_to_gl(ty);
+            let (dispatch, _, _) = vertex_location_type_to_gl(attribute.ty);
+            let column_stride = column_byte_size(data_type, count);
+            let locations = attribute_location_count(attribute.ty, attribute.size);
+
+            for column in 0..locations {
+                attributes.push(VaoAttribute {
+                    location: attribute.location + column as i32,
+                    dispatch: dispatch,
+                    data_type: data_type,
+                    count: count,
+                    normalized: normalized,
+                    relative_offset: offset as u32 + (column * column_stride) as u32,
+                    binding: binding_index as u32,
+                });
+            }
+        }
+    }
+
+    VaoFormat { attributes: attributes, bindings: bindings }
+}
+
+/// Returns the number of consecutive vertex attribute locations a program attribute occupies:
+/// the column count for a matrix type, multiplied by `size` for an array of them (eg. `in
+/// mat4 foo[2]` spans `4 * 2` locations); `size` alone for a non-matrix array such as `in vec4
+/// foo[4]`.
+fn attribute_location_count(ty: AttributeType, size: i32) -> usize {
+    let elements = if size > 1 { size as usize } else { 1 };
+
+    match matrix_shape(ty) {
+        Some((columns, _, _)) => columns * elements,
+        None => elements,
+    }
+}
+
+/// For a matrix `AttributeType`, returns `(columns, rows, column component GL type)`. A matrix
+/// attribute occupies one consecutive vertex attribute location per column, with each location
+/// holding `rows` components of the returned GL type.
+fn matrix_shape(ty: AttributeType) -> Option<(usize, gl::types::GLint, gl::types::GLenum)> {
+    match ty {
+        AttributeType::F32x2x2 => Some((2, 2, gl::FLOAT)),
+        AttributeType::F32x2x3 => Some((2, 3, gl::FLOAT)),
+        AttributeType::F32x2x4 => Some((2, 4, gl::FLOAT)),
+        AttributeType::F32x3x2 => Some((3, 2, gl::FLOAT)),
+        AttributeType::F32x3x3 => Some((3, 3, gl::FLOAT)),
+        AttributeType::F32x3x4 => Some((3, 4, gl::FLOAT)),
+        AttributeType::F32x4x2 => Some((4, 2, gl::FLOAT)),
+        AttributeType::F32x4x3 => Some((4, 3, gl::FLOAT)),
+        AttributeType::F32x4x4 => Some((4, 4, gl::FLOAT)),
+        AttributeType::F64x2x2 => Some((2, 2, gl::DOUBLE)),
+        AttributeType::F64x2x3 => Some((2, 3, gl::DOUBLE)),
+        AttributeType::F64x2x4 => Some((2, 4, gl::DOUBLE)),
+        AttributeType::F64x3x2 => Some((3, 2, gl::DOUBLE)),
+        AttributeType::F64x3x3 => Some((3, 3, gl::DOUBLE)),
+        AttributeType::F64x3x4 => Some((3, 4, gl::DOUBLE)),
+        AttributeType::F64x4x2 => Some((4, 2, gl::DOUBLE)),
+        AttributeType::F64x4x3 => Some((4, 3, gl::DOUBLE)),
+        AttributeType::F64x4x4 => Some((4, 4, gl::DOUBLE)),
+        _ => None,
+    }
+}
+
+/// Returns the GL type, component count and normalization flag for a single vertex attribute
+/// *location*. For most types this is the same as `vertex_binding_type_to_gl`; for a matrix
+/// type, which spans several locations, this describes a single column rather than the whole
+/// matrix (which isn't itself a valid `glVertexAttribPointer`/`Format` argument).
+fn vertex_location_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types::GLint, bool) {
+    match matrix_shape(ty) {
+        Some((_, rows, component_ty)) => (component_ty, rows, false),
+        None => vertex_binding_type_to_gl(ty),
+    }
+}
+
+/// Returns the size in bytes of a single scalar of the given GL type.
+///
+/// # Panics
+///
+/// Panics if `ty` is a packed format such as `*_2_10_10_10_REV`: those aren't made of
+/// `count` scalars of this size, use `column_byte_size` for them instead.
+fn gl_type_byte_size(ty: gl::types::GLenum) -> usize {
+    match ty {
+        gl::BYTE | gl::UNSIGNED_BYTE => 1,
+        gl::SHORT | gl::UNSIGNED_SHORT => 2,
+        gl::INT | gl::UNSIGNED_INT | gl::FLOAT => 4,
+        gl::DOUBLE => 8,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns the byte size of one `(data_type, count)` column as passed to
+/// `VertexAttribPointer`/`Format`, used to compute the stride between consecutive columns of a
+/// matrix attribute. Packed types such as `UNSIGNED_INT_2_10_10_10_REV` report `count == 4` to
+/// satisfy the GL API's `size` argument, but the whole value is a single 4-byte word, not
+/// `count` separate scalars. This is computed for every attribute, so non-matrix packed
+/// attributes go through that branch too (matrix types never use a packed column type).
+fn column_byte_size(data_type: gl::types::GLenum, count: gl::types::GLint) -> usize {
+    match data_type {
+        gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => 4,
+        _ => count as usize * gl_type_byte_size(data_type),
+    }
+}
+
 impl VertexArrayObject {
     /// Builds a new `VertexArrayObject`.
     ///
     /// The vertex buffer, index buffer and program must not outlive the
     /// VAO, and the VB & program attributes must not change.
     unsafe fn new(mut ctxt: &mut CommandContext,
-                  vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, Option<u32>)],
+                  vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
                   ib_id: gl::types::GLuint, program: &Program) -> VertexArrayObject
     {
-        // checking the attributes types
-        for &(_, ref bindings, _, _) in vertex_buffers {
-            for &(ref name, _, ty) in bindings {
-                let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
-                    Some(a) => a,
-                    None => continue
-                };
-
-                if ty.get_num_components() != attribute.ty.get_num_components() ||
-                    attribute.size != 1
-                {
-                    panic!("The program attribute `{}` does not match the vertex format. \
-                            Program expected {:?}, got {:?}.", name, attribute.ty, ty);
-                }
-            }
-        }
-
-        // checking for missing attributes
-        for (&ref name, _) in program.attributes() {
-            let mut found = false;
-            for &(_, ref bindings, _, _) in vertex_buffers {
-                if bindings.iter().find(|&&(ref n, _, _)| n == name).is_some() {
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                panic!("The program attribute `{}` is missing in the vertex bindings", name);
-            }
-        };
-
-        // TODO: check for collisions between the vertices sources
+        validate_bindings(vertex_buffers, program);
 
         // building the VAO
-        let id = {
-            let mut id = mem::uninitialized();
-            if ctxt.version >= &Version(Api::Gl, 3, 0) ||
-                ctxt.version >= &Version(Api::GlEs, 3, 0) ||
-                ctxt.extensions.gl_arb_vertex_array_object
-            {
-                ctxt.gl.GenVertexArrays(1, &mut id);
-            } else if ctxt.extensions.gl_oes_vertex_array_object {
-                ctxt.gl.GenVertexArraysOES(1, &mut id);
-            } else if ctxt.extensions.gl_apple_vertex_array_object {
-                ctxt.gl.GenVertexArraysAPPLE(1, &mut id);
-            } else {
-                unreachable!();
-            };
-            id
-        };
+        let id = generate_vao(ctxt);
 
         // we don't use DSA as we're going to make multiple calls for this VAO
         // and we're likely going to use the VAO right after it's been created
@@ -222,18 +521,9 @@ impl VertexArrayObject {
 
         // binding index buffer
         // the ELEMENT_ARRAY_BUFFER is part of the state of the VAO
-        // TODO: use a proper function
-        if ctxt.version >= &Version(Api::Gl, 1, 5) ||
-            ctxt.version >= &Version(Api::GlEs, 2, 0)
-        {
-            ctxt.gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ib_id);
-        } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-            ctxt.gl.BindBufferARB(gl::ELEMENT_ARRAY_BUFFER_ARB, ib_id);
-        } else {
-            unreachable!();
-        }
+        bind_element_array_buffer(ctxt, ib_id);
 
-        for &(vertex_buffer, ref bindings, stride, divisor) in vertex_buffers {
+        for &(vertex_buffer, ref bindings, stride, first, divisor) in vertex_buffers {
             // glVertexAttribPointer uses the current array buffer
             // TODO: use a proper function
             if ctxt.state.array_buffer_binding != vertex_buffer {
@@ -249,44 +539,104 @@ impl VertexArrayObject {
                 ctxt.state.array_buffer_binding = vertex_buffer;
             }
 
+            // offset, in bytes, of the first element of the slice we want to bind
+            let first_offset = first * stride;
+
             // binding attributes
             for &(ref name, offset, ty) in bindings {
-                let (data_type, elements_count) = vertex_binding_type_to_gl(ty);
+                let (data_type, elements_count, normalized) = vertex_location_type_to_gl(ty);
 
                 let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
                     Some(a) => a,
                     None => continue
                 };
 
-                let (attribute_ty, _) = vertex_binding_type_to_gl(attribute.ty);
+                let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
+                let column_stride = column_byte_size(data_type, elements_count);
+                let locations = attribute_location_count(attribute.ty, attribute.size);
+                let packed = data_type == gl::INT_2_10_10_10_REV ||
+                             data_type == gl::UNSIGNED_INT_2_10_10_10_REV;
+
+                let offset = offset + first_offset;
 
                 if attribute.location != -1 {
-                    match attribute_ty {
-                        gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
-                        gl::INT | gl::UNSIGNED_INT =>
-                            ctxt.gl.VertexAttribIPointer(attribute.location as u32,
-                                elements_count as gl::types::GLint, data_type,
-                                stride as i32, offset as *const libc::c_void),
-
-                        gl::DOUBLE | gl::DOUBLE_VEC2 | gl::DOUBLE_VEC3 | gl::DOUBLE_VEC4 |
-                        gl::DOUBLE_MAT2 | gl::DOUBLE_MAT3 | gl::DOUBLE_MAT4 |
-                        gl::DOUBLE_MAT2x3 | gl::DOUBLE_MAT2x4 | gl::DOUBLE_MAT3x2 |
-                        gl::DOUBLE_MAT3x4 | gl::DOUBLE_MAT4x2 | gl::DOUBLE_MAT4x3 =>
-                            ctxt.gl.VertexAttribLPointer(attribute.location as u32,
-                                elements_count as gl::types::GLint, data_type,
-                                stride as i32, offset as *const libc::c_void),
-
-                        _ => ctxt.gl.VertexAttribPointer(attribute.location as u32,
-                                elements_count as gl::types::GLint, data_type, 0,
-                                stride as i32, offset as *const libc::c_void)
+                    for column in 0..locations {
+                        let location = attribute.location as u32 + column as u32;
+                        let column_offset = offset + column * column_stride;
+
+                        match attribute_ty {
+                            gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
+                            gl::INT | gl::UNSIGNED_INT if !packed =>
+                                ctxt.gl.VertexAttribIPointer(location,
+                                    elements_count as gl::types::GLint, data_type,
+                                    stride as i32, column_offset as *const libc::c_void),
+
+                            gl::DOUBLE | gl::DOUBLE_VEC2 | gl::DOUBLE_VEC3 | gl::DOUBLE_VEC4 |
+                            gl::DOUBLE_MAT2 | gl::DOUBLE_MAT3 | gl::DOUBLE_MAT4 |
+                            gl::DOUBLE_MAT2x3 | gl::DOUBLE_MAT2x4 | gl::DOUBLE_MAT3x2 |
+                            gl::DOUBLE_MAT3x4 | gl::DOUBLE_MAT4x2 | gl::DOUBLE_MAT4x3 =>
+                                ctxt.gl.VertexAttribLPointer(location,
+                                    elements_count as gl::types::GLint, data_type,
+                                    stride as i32, column_offset as *const libc::c_void),
+
+                            _ => ctxt.gl.VertexAttribPointer(location,
+                                    elements_count as gl::types::GLint, data_type,
+                                    if normalized { gl::TRUE } else { gl::FALSE },
+                                    stride as i32, column_offset as *const libc::c_void)
+                        }
+
+                        if let Some(divisor) = divisor {
+                            ctxt.gl.VertexAttribDivisor(location, divisor);
+                        }
+
+                        ctxt.gl.EnableVertexAttribArray(location);
                     }
+                }
+            }
+        }
 
-                    if let Some(divisor) = divisor {
-                        ctxt.gl.VertexAttribDivisor(attribute.location as u32, divisor);
-                    }
+        VertexArrayObject {
+            id: id,
+            destroyed: false,
+        }
+    }
 
-                    ctxt.gl.EnableVertexAttribArray(attribute.location as u32);
-                }
+    /// Builds a format-only VAO using `ARB_vertex_attrib_binding`: attribute formats and
+    /// bindings are set up, but no buffer is attached yet. `glBindVertexBuffer` is called
+    /// separately, every time the VAO is used, to attach the buffers for that draw.
+    unsafe fn new_fast_path(mut ctxt: &mut CommandContext, format: &VaoFormat) -> VertexArrayObject {
+        let id = generate_vao(ctxt);
+        bind_vao(&mut ctxt, id);
+
+        for attribute in &format.attributes {
+            match attribute.dispatch {
+                gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
+                gl::INT | gl::UNSIGNED_INT
+                    if attribute.data_type != gl::INT_2_10_10_10_REV &&
+                       attribute.data_type != gl::UNSIGNED_INT_2_10_10_10_REV =>
+                    ctxt.gl.VertexAttribIFormat(attribute.location as u32, attribute.count,
+                        attribute.data_type, attribute.relative_offset),
+
+                gl::DOUBLE | gl::DOUBLE_VEC2 | gl::DOUBLE_VEC3 | gl::DOUBLE_VEC4 |
+                gl::DOUBLE_MAT2 | gl::DOUBLE_MAT3 | gl::DOUBLE_MAT4 |
+                gl::DOUBLE_MAT2x3 | gl::DOUBLE_MAT2x4 | gl::DOUBLE_MAT3x2 |
+                gl::DOUBLE_MAT3x4 | gl::DOUBLE_MAT4x2 | gl::DOUBLE_MAT4x3 =>
+                    ctxt.gl.VertexAttribLFormat(attribute.location as u32, attribute.count,
+                        attribute.data_type, attribute.relative_offset),
+
+                _ => ctxt.gl.VertexAttribFormat(attribute.location as u32, attribute.count,
+                        attribute.data_type,
+                        if attribute.normalized { gl::TRUE } else { gl::FALSE },
+                        attribute.relative_offset),
+            }
+
+            ctxt.gl.VertexAttribBinding(attribute.location as u32, attribute.binding);
+            ctxt.gl.EnableVertexAttribArray(attribute.location as u32);
+        }
+
+        for (binding, &(_, divisor)) in format.bindings.iter().enumerate() {
+            if let Some(divisor) = divisor {
+                ctxt.gl.VertexBindingDivisor(binding as u32, divisor);
             }
         }
 
@@ -339,58 +689,100 @@ impl GlObject for VertexArrayObject {
     }
 }
 
-fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types::GLint) {
+/// Generates a new vertex array object name, dispatching to whichever variant of the
+/// extension/version is available (mirrors the dispatch style of `bind_vao`/`destroy`).
+unsafe fn generate_vao(ctxt: &mut CommandContext) -> gl::types::GLuint {
+    let mut id = mem::uninitialized();
+    if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+        ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+        ctxt.extensions.gl_arb_vertex_array_object
+    {
+        ctxt.gl.GenVertexArrays(1, &mut id);
+    } else if ctxt.extensions.gl_oes_vertex_array_object {
+        ctxt.gl.GenVertexArraysOES(1, &mut id);
+    } else if ctxt.extensions.gl_apple_vertex_array_object {
+        ctxt.gl.GenVertexArraysAPPLE(1, &mut id);
+    } else {
+        unreachable!();
+    };
+    id
+}
+
+/// Binds a buffer as the current `ELEMENT_ARRAY_BUFFER`, which becomes part of the state of
+/// whichever VAO is currently bound.
+unsafe fn bind_element_array_buffer(ctxt: &mut CommandContext, ib_id: gl::types::GLuint) {
+    // TODO: use a proper function
+    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+        ctxt.version >= &Version(Api::GlEs, 2, 0)
+    {
+        ctxt.gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ib_id);
+    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+        ctxt.gl.BindBufferARB(gl::ELEMENT_ARRAY_BUFFER_ARB, ib_id);
+    } else {
+        unreachable!();
+    }
+}
+
+/// Returns the GL type, component count and whether the type should be read as normalized
+/// floats for a given `AttributeType`.
+fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types::GLint, bool) {
     match ty {
-        AttributeType::I8 => (gl::BYTE, 1),
-        AttributeType::I8I8 => (gl::BYTE, 2),
-        AttributeType::I8I8I8 => (gl::BYTE, 3),
-        AttributeType::I8I8I8I8 => (gl::BYTE, 4),
-        AttributeType::U8 => (gl::UNSIGNED_BYTE, 1),
-        AttributeType::U8U8 => (gl::UNSIGNED_BYTE, 2),
-        AttributeType::U8U8U8 => (gl::UNSIGNED_BYTE, 3),
-        AttributeType::U8U8U8U8 => (gl::UNSIGNED_BYTE, 4),
-        AttributeType::I16 => (gl::SHORT, 1),
-        AttributeType::I16I16 => (gl::SHORT, 2),
-        AttributeType::I16I16I16 => (gl::SHORT, 3),
-        AttributeType::I16I16I16I16 => (gl::SHORT, 4),
-        AttributeType::U16 => (gl::UNSIGNED_SHORT, 1),
-        AttributeType::U16U16 => (gl::UNSIGNED_SHORT, 2),
-        AttributeType::U16U16U16 => (gl::UNSIGNED_SHORT, 3),
-        AttributeType::U16U16U16U16 => (gl::UNSIGNED_SHORT, 4),
-        AttributeType::I32 => (gl::INT, 1),
-        AttributeType::I32I32 => (gl::INT, 2),
-        AttributeType::I32I32I32 => (gl::INT, 3),
-        AttributeType::I32I32I32I32 => (gl::INT, 4),
-        AttributeType::U32 => (gl::UNSIGNED_INT, 1),
-        AttributeType::U32U32 => (gl::UNSIGNED_INT, 2),
-        AttributeType::U32U32U32 => (gl::UNSIGNED_INT, 3),
-        AttributeType::U32U32U32U32 => (gl::UNSIGNED_INT, 4),
-        AttributeType::F32 => (gl::FLOAT, 1),
-        AttributeType::F32F32 => (gl::FLOAT, 2),
-        AttributeType::F32F32F32 => (gl::FLOAT, 3),
-        AttributeType::F32F32F32F32 => (gl::FLOAT, 4),
-        AttributeType::F32x2x2 => (gl::FLOAT_MAT2, 1),
-        AttributeType::F32x2x3 => (gl::FLOAT_MAT2x3, 1),
-        AttributeType::F32x2x4 => (gl::FLOAT_MAT2x4, 1),
-        AttributeType::F32x3x2 => (gl::FLOAT_MAT3x2, 1),
-        AttributeType::F32x3x3 => (gl::FLOAT_MAT3, 1),
-        AttributeType::F32x3x4 => (gl::FLOAT_MAT3x4, 1),
-        AttributeType::F32x4x2 => (gl::FLOAT_MAT4x2, 1),
-        AttributeType::F32x4x3 => (gl::FLOAT_MAT4x3, 1),
-        AttributeType::F32x4x4 => (gl::FLOAT_MAT4, 1),
-        AttributeType::F64 => (gl::DOUBLE, 1),
-        AttributeType::F64F64 => (gl::DOUBLE, 2),
-        AttributeType::F64F64F64 => (gl::DOUBLE, 3),
-        AttributeType::F64F64F64F64 => (gl::DOUBLE, 4),
-        AttributeType::F64x2x2 => (gl::DOUBLE_MAT2, 1),
-        AttributeType::F64x2x3 => (gl::DOUBLE_MAT2x3, 1),
-        AttributeType::F64x2x4 => (gl::DOUBLE_MAT2x4, 1),
-        AttributeType::F64x3x2 => (gl::DOUBLE_MAT3x2, 1),
-        AttributeType::F64x3x3 => (gl::DOUBLE_MAT3, 1),
-        AttributeType::F64x3x4 => (gl::DOUBLE_MAT3x4, 1),
-        AttributeType::F64x4x2 => (gl::DOUBLE_MAT4x2, 1),
-        AttributeType::F64x4x3 => (gl::DOUBLE_MAT4x3, 1),
-        AttributeType::F64x4x4 => (gl::DOUBLE_MAT4, 1),
+        AttributeType::I8 => (gl::BYTE, 1, false),
+        AttributeType::I8I8 => (gl::BYTE, 2, false),
+        AttributeType::I8I8I8 => (gl::BYTE, 3, false),
+        AttributeType::I8I8I8I8 => (gl::BYTE, 4, false),
+        AttributeType::I8I8I8I8Norm => (gl::BYTE, 4, true),
+        AttributeType::U8 => (gl::UNSIGNED_BYTE, 1, false),
+        AttributeType::U8U8 => (gl::UNSIGNED_BYTE, 2, false),
+        AttributeType::U8U8U8 => (gl::UNSIGNED_BYTE, 3, false),
+        AttributeType::U8U8U8U8 => (gl::UNSIGNED_BYTE, 4, false),
+        AttributeType::U8U8U8U8Norm => (gl::UNSIGNED_BYTE, 4, true),
+        AttributeType::I16 => (gl::SHORT, 1, false),
+        AttributeType::I16I16 => (gl::SHORT, 2, false),
+        AttributeType::I16I16I16 => (gl::SHORT, 3, false),
+        AttributeType::I16I16I16I16 => (gl::SHORT, 4, false),
+        AttributeType::U16 => (gl::UNSIGNED_SHORT, 1, false),
+        AttributeType::U16U16 => (gl::UNSIGNED_SHORT, 2, false),
+        AttributeType::U16U16U16 => (gl::UNSIGNED_SHORT, 3, false),
+        AttributeType::U16U16U16U16 => (gl::UNSIGNED_SHORT, 4, false),
+        AttributeType::I32 => (gl::INT, 1, false),
+        AttributeType::I32I32 => (gl::INT, 2, false),
+        AttributeType::I32I32I32 => (gl::INT, 3, false),
+        AttributeType::I32I32I32I32 => (gl::INT, 4, false),
+        AttributeType::U32 => (gl::UNSIGNED_INT, 1, false),
+        AttributeType::U32U32 => (gl::UNSIGNED_INT, 2, false),
+        AttributeType::U32U32U32 => (gl::UNSIGNED_INT, 3, false),
+        AttributeType::U32U32U32U32 => (gl::UNSIGNED_INT, 4, false),
+        // packed formats: a single machine word holding 4 components, always normalized and
+        // always routed through `VertexAttribPointer` (never the integer `*IPointer` path)
+        AttributeType::I32_2_10_10_10Norm => (gl::INT_2_10_10_10_REV, 4, true),
+        AttributeType::U32_2_10_10_10Norm => (gl::UNSIGNED_INT_2_10_10_10_REV, 4, true),
+        AttributeType::F32 => (gl::FLOAT, 1, false),
+        AttributeType::F32F32 => (gl::FLOAT, 2, false),
+        AttributeType::F32F32F32 => (gl::FLOAT, 3, false),
+        AttributeType::F32F32F32F32 => (gl::FLOAT, 4, false),
+        AttributeType::F32x2x2 => (gl::FLOAT_MAT2, 1, false),
+        AttributeType::F32x2x3 => (gl::FLOAT_MAT2x3, 1, false),
+        AttributeType::F32x2x4 => (gl::FLOAT_MAT2x4, 1, false),
+        AttributeType::F32x3x2 => (gl::FLOAT_MAT3x2, 1, false),
+        AttributeType::F32x3x3 => (gl::FLOAT_MAT3, 1, false),
+        AttributeType::F32x3x4 => (gl::FLOAT_MAT3x4, 1, false),
+        AttributeType::F32x4x2 => (gl::FLOAT_MAT4x2, 1, false),
+        AttributeType::F32x4x3 => (gl::FLOAT_MAT4x3, 1, false),
+        AttributeType::F32x4x4 => (gl::FLOAT_MAT4, 1, false),
+        AttributeType::F64 => (gl::DOUBLE, 1, false),
+        AttributeType::F64F64 => (gl::DOUBLE, 2, false),
+        AttributeType::F64F64F64 => (gl::DOUBLE, 3, false),
+        AttributeType::F64F64F64F64 => (gl::DOUBLE, 4, false),
+        AttributeType::F64x2x2 => (gl::DOUBLE_MAT2, 1, false),
+        AttributeType::F64x2x3 => (gl::DOUBLE_MAT2x3, 1, false),
+        AttributeType::F64x2x4 => (gl::DOUBLE_MAT2x4, 1, false),
+        AttributeType::F64x3x2 => (gl::DOUBLE_MAT3x2, 1, false),
+        AttributeType::F64x3x3 => (gl::DOUBLE_MAT3, 1, false),
+        AttributeType::F64x3x4 => (gl::DOUBLE_MAT3x4, 1, false),
+        AttributeType::F64x4x2 => (gl::DOUBLE_MAT4x2, 1, false),
+        AttributeType::F64x4x3 => (gl::DOUBLE_MAT4x3, 1, false),
+        AttributeType::F64x4x4 => (gl::DOUBLE_MAT4, 1, false),
     }
 }
 